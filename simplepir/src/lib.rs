@@ -1,9 +1,162 @@
 use nalgebra::{DMatrix, DVector};
 use num_bigint::{BigInt, RandBigInt};
-use num_traits::{One, Signed, Zero};
+use num_traits::{One, Signed, ToPrimitive, Zero};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
 use rand_distr::{Distribution, Normal};
+use rayon::prelude::*;
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
+/// Fast fixed-width modular arithmetic for `SimplePIRParams::q`, used in the `gen_hint` /
+/// `encrypt` / `process_query` / `recover` hot loops so they run as native `u64`/`u128` math
+/// instead of `num_bigint` divisions on every multiply-accumulate. `BigInt` stays the type at
+/// the public API boundary (`SimplePIRParams::q`, the `DMatrix<BigInt>` inputs/outputs); these
+/// types only live inside the functions below.
+#[derive(Debug, Clone, Copy)]
+enum Modulus {
+    /// `q = 2^64`: the only modulus `gen_params` actually produces. Reduction is exactly
+    /// native `u64` wraparound, so there is no reduction step to run at all.
+    PowerOfTwo64,
+    /// Any other modulus, reduced via Barrett's algorithm.
+    Arbitrary(Barrett),
+}
+
+impl Modulus {
+    fn from_bigint(q: &BigInt) -> Self {
+        if *q == BigInt::one() << 64 {
+            Modulus::PowerOfTwo64
+        } else {
+            let q_u64 = q
+                .to_u64()
+                .expect("the Zq fast path requires a modulus that fits in 64 bits");
+            Modulus::Arbitrary(Barrett::new(q_u64))
+        }
+    }
+}
+
+/// Barrett reduction constants for a 64-bit modulus `q`: `mu = floor(2^128 / q)`, precomputed
+/// once so reducing a product `< q^2` costs a couple of 128-bit multiplies instead of a
+/// division.
+#[derive(Debug, Clone, Copy)]
+struct Barrett {
+    q: u64,
+    mu: u128,
+}
+
+/// The high 128 bits of the full 256-bit product `x * y`, computed via schoolbook
+/// multiplication on 64-bit limbs since Rust has no native 128x128->256 multiply.
+fn mulhi_u128(x: u128, y: u128) -> u128 {
+    let x0 = x as u64 as u128;
+    let x1 = x >> 64;
+    let y0 = y as u64 as u128;
+    let y1 = y >> 64;
+
+    let p00 = x0 * y0;
+    let p01 = x0 * y1;
+    let p10 = x1 * y0;
+    let p11 = x1 * y1;
+
+    let mid = (p00 >> 64) + (p01 & u64::MAX as u128) + (p10 & u64::MAX as u128);
+    let carry = mid >> 64;
+
+    p11.wrapping_add(p01 >> 64)
+        .wrapping_add(p10 >> 64)
+        .wrapping_add(carry)
+}
+
+impl Barrett {
+    fn new(q: u64) -> Self {
+        assert!(q > 1, "Barrett reduction needs a modulus greater than 1");
+        // `mu` must be exactly `floor(2^128 / q)`, but `2^128` itself overflows `u128`. Compute
+        // it from `u128::MAX = 2^128 - 1` instead: `floor((2^128 - 1) / q)` already equals
+        // `floor(2^128 / q)` unless `q` divides `2^128` evenly, in which case it's one short.
+        let q128 = q as u128;
+        let base = u128::MAX / q128;
+        let rem = u128::MAX % q128;
+        let mu = if rem == q128 - 1 { base + 1 } else { base };
+        Self { q, mu }
+    }
+
+    /// Reduce `x < q^2` mod `q`.
+    fn reduce(&self, x: u128) -> u64 {
+        let q = self.q as u128;
+        let t = mulhi_u128(x, self.mu);
+        let mut r = x.wrapping_sub(t.wrapping_mul(q));
+        if r >= q {
+            r -= q;
+        }
+        if r >= q {
+            r -= q;
+        }
+        r as u64
+    }
+}
+
+/// An element of `Z_q` backed by a native `u64`. All arithmetic below reduces via `modulus`,
+/// which is a no-op truncation for the common `q = 2^64` case and Barrett reduction otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Zq(u64);
+
+impl Zq {
+    fn from_bigint(x: &BigInt, modulus: &Modulus) -> Self {
+        match modulus {
+            Modulus::PowerOfTwo64 => {
+                let (sign, bytes) = x.to_bytes_le();
+                let mut buf = [0u8; 8];
+                let n = bytes.len().min(8);
+                buf[..n].copy_from_slice(&bytes[..n]);
+                let mag = u64::from_le_bytes(buf);
+                Zq(if sign == num_bigint::Sign::Minus {
+                    mag.wrapping_neg()
+                } else {
+                    mag
+                })
+            }
+            Modulus::Arbitrary(barrett) => {
+                let q = BigInt::from(barrett.q);
+                let reduced = ((x % &q) + &q) % &q;
+                Zq(reduced.to_u64().unwrap())
+            }
+        }
+    }
+
+    fn to_bigint(self) -> BigInt {
+        BigInt::from(self.0)
+    }
+
+    fn add(self, other: Self, modulus: &Modulus) -> Self {
+        match modulus {
+            Modulus::PowerOfTwo64 => Zq(self.0.wrapping_add(other.0)),
+            Modulus::Arbitrary(barrett) => Zq(barrett.reduce(self.0 as u128 + other.0 as u128)),
+        }
+    }
+
+    fn sub(self, other: Self, modulus: &Modulus) -> Self {
+        match modulus {
+            Modulus::PowerOfTwo64 => Zq(self.0.wrapping_sub(other.0)),
+            Modulus::Arbitrary(barrett) => {
+                let q = barrett.q as u128;
+                Zq(barrett.reduce(self.0 as u128 + q - other.0 as u128))
+            }
+        }
+    }
+
+    /// `self + a * b mod q`, the inner-loop update shared by every matrix/vector product below.
+    fn mul_add(self, a: Self, b: Self, modulus: &Modulus) -> Self {
+        match modulus {
+            Modulus::PowerOfTwo64 => Zq(self.0.wrapping_add(a.0.wrapping_mul(b.0))),
+            Modulus::Arbitrary(barrett) => {
+                let prod = barrett.reduce(a.0 as u128 * b.0 as u128);
+                Zq(barrett.reduce(self.0 as u128 + prod as u128))
+            }
+        }
+    }
+
+    fn mul(self, other: Self, modulus: &Modulus) -> Self {
+        Zq::default().mul_add(self, other, modulus)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct SimplePIRParams {
@@ -13,14 +166,62 @@ pub struct SimplePIRParams {
     pub p: BigInt, // Plaintext modulus
     std_dev: f64,  // Standard deviation for error
     seed: u64,     // Random seed for reproducibility
+    modulus: Modulus, // Fast Zq reduction strategy for `q`, precomputed once here
+}
+
+/// Wire-format twin of [`SimplePIRParams`]: `q`/`p` as decimal strings (`BigInt` has no serde
+/// impl here), and no `modulus` field at all, since it's cheap to recompute from `q` on
+/// deserialize rather than ship redundantly.
+#[derive(Serialize, Deserialize)]
+struct SimplePIRParamsWire {
+    n: usize,
+    m: usize,
+    q: String,
+    p: String,
+    std_dev: f64,
+    seed: u64,
+}
+
+impl Serialize for SimplePIRParams {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SimplePIRParamsWire {
+            n: self.n,
+            m: self.m,
+            q: self.q.to_string(),
+            p: self.p.to_string(),
+            std_dev: self.std_dev,
+            seed: self.seed,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SimplePIRParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let wire = SimplePIRParamsWire::deserialize(deserializer)?;
+        let q = BigInt::from_str(&wire.q).map_err(D::Error::custom)?;
+        let p = BigInt::from_str(&wire.p).map_err(D::Error::custom)?;
+        let modulus = Modulus::from_bigint(&q);
+        Ok(SimplePIRParams {
+            n: wire.n,
+            m: wire.m,
+            q,
+            p,
+            std_dev: wire.std_dev,
+            seed: wire.seed,
+            modulus,
+        })
+    }
 }
 
 pub fn gen_params(m: usize, n: usize, mod_power: u32) -> SimplePIRParams {
     let mut rng = rand::thread_rng();
+    let q = BigInt::one() << 64;
     SimplePIRParams {
         n,
         m,
-        q: BigInt::one() << 64,
+        modulus: Modulus::from_bigint(&q),
+        q,
         p: BigInt::one() << mod_power,
         std_dev: 3.2,
         seed: rng.gen(),
@@ -33,6 +234,13 @@ pub fn gen_matrix_a(seed: u64, m: usize, n: usize, q: u64) -> DMatrix<BigInt> {
     DMatrix::from_vec(m, n, data)
 }
 
+/// Regenerate the public matrix `A` from `params` alone, exactly as [`gen_hint`] does
+/// internally. `A` is fully determined by `params.seed`/`m`/`n`/`q`, so a holder of `params`
+/// never needs to receive `A` itself over the wire.
+pub fn regenerate_matrix_a(params: &SimplePIRParams) -> DMatrix<BigInt> {
+    gen_matrix_a(params.seed, params.m, params.n, params.q.bits())
+}
+
 pub fn gen_secret(q: u64, n: usize, seed: Option<u64>) -> DVector<BigInt> {
     let mut rng = match seed {
         Some(s) => ChaCha20Rng::seed_from_u64(s),
@@ -48,21 +256,30 @@ pub fn gen_hint(
     db: &DMatrix<BigInt>,
 ) -> (DMatrix<BigInt>, DMatrix<BigInt>) {
     let a = gen_matrix_a(params.seed, params.m, params.n, params.q.bits());
-    let modulus = &params.q.clone();
-
-    // Matrix multiplication with modulo
-    let mut hint = DMatrix::zeros(db.nrows(), a.ncols());
-    for i in 0..db.nrows() {
-        for j in 0..a.ncols() {
-            let mut sum = BigInt::zero();
-            for k in 0..db.ncols() {
-                sum = (sum + (db[(i, k)].clone() * a[(k, j)].clone()) % modulus) % modulus;
-            }
-            hint[(i, j)] = sum;
-        }
-    }
-
-    (hint, a)
+    let modulus = &params.modulus;
+
+    let db_zq = db.map(|x| Zq::from_bigint(&x, modulus));
+    let a_zq = a.map(|x| Zq::from_bigint(&x, modulus));
+
+    // Each output row only reads its own row of `db_zq` against the whole of `a_zq`, so rows
+    // are independent and can be scanned across worker threads with no locking; rayon splits
+    // the row range over whatever thread pool (global, or a caller-installed one) is active.
+    let flat: Vec<BigInt> = (0..db.nrows())
+        .into_par_iter()
+        .flat_map(|i| {
+            (0..a.ncols())
+                .map(|j| {
+                    let mut sum = Zq::default();
+                    for k in 0..db.ncols() {
+                        sum = sum.mul_add(db_zq[(i, k)], a_zq[(k, j)], modulus);
+                    }
+                    sum.to_bigint()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    (DMatrix::from_row_slice(db.nrows(), a.ncols(), &flat), a)
 }
 
 pub fn encrypt(
@@ -71,38 +288,43 @@ pub fn encrypt(
     a: &DMatrix<BigInt>,
     s: &DVector<BigInt>,
 ) -> DVector<BigInt> {
-    let modulus = &params.q.clone();
-    let delta = modulus / &params.p;
+    let modulus = &params.modulus;
+    let delta = Zq::from_bigint(&(&params.q / &params.p), modulus);
 
     // Generate Gaussian error
     let normal = Normal::new(0.0, params.std_dev).unwrap();
     let mut rng = rand::thread_rng();
-    let e: DVector<BigInt> = DVector::from_iterator(
-        params.m,
-        (0..params.m)
-            .map(|_| (BigInt::from(normal.sample(&mut rng).round() as i64) * &params.p) % modulus),
-    );
+    let e: Vec<Zq> = (0..params.m)
+        .map(|_| {
+            let e_val = BigInt::from(normal.sample(&mut rng).round() as i64) * &params.p;
+            Zq::from_bigint(&e_val, modulus)
+        })
+        .collect();
+
+    let a_zq = a.map(|x| Zq::from_bigint(&x, modulus));
+    let s_zq: Vec<Zq> = s.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+    let v_zq: Vec<Zq> = v.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
 
     // Compute As
-    let mut as_prod = DVector::zeros(params.m);
+    let mut as_prod = vec![Zq::default(); params.m];
     for i in 0..params.m {
-        let mut sum = BigInt::zero();
+        let mut sum = Zq::default();
         for j in 0..params.n {
-            sum = (sum + (&a[(i, j)] * &s[j]) % modulus) % modulus
+            sum = sum.mul_add(a_zq[(i, j)], s_zq[j], modulus);
         }
         as_prod[i] = sum;
     }
 
-    let mut result = DVector::<BigInt>::zeros(params.m);
-    result
-        .iter_mut()
-        .zip(as_prod.iter().zip(e.iter().zip(v.iter())))
-        .for_each(|(res, (as_val, (e_val, v_val)))| {
-            let scaled_v = (&delta * v_val) % modulus;
-            *res = (as_val + e_val + scaled_v) % modulus;
-        });
+    let result: Vec<BigInt> = as_prod
+        .iter()
+        .zip(e.iter().zip(v_zq.iter()))
+        .map(|(as_val, (e_val, v_val))| {
+            let scaled_v = delta.mul(*v_val, modulus);
+            as_val.add(*e_val, modulus).add(scaled_v, modulus).to_bigint()
+        })
+        .collect();
 
-    result
+    DVector::from_vec(result)
 }
 
 pub fn generate_query(
@@ -119,16 +341,71 @@ pub fn generate_query(
 }
 
 pub fn process_query(db: &DMatrix<BigInt>, query: &DVector<BigInt>, q: BigInt) -> DVector<BigInt> {
-    let mut result = DVector::zeros(db.nrows());
-    let modulus = &q.clone();
-    for i in 0..db.nrows() {
-        let mut sum = BigInt::zero();
-        for j in 0..db.ncols() {
-            sum = (sum + (&db[(i, j)] * &query[j]) % modulus) % modulus;
-        }
-        result[i] = sum;
+    let modulus = Modulus::from_bigint(&q);
+    let db_zq = db.map(|x| Zq::from_bigint(&x, &modulus));
+    let query_zq: Vec<Zq> = query.iter().map(|x| Zq::from_bigint(x, &modulus)).collect();
+
+    // Each output entry is an independent dot product over its own row of `db_zq`, so rows
+    // can be scanned in parallel across worker threads with no locking.
+    let result: Vec<BigInt> = (0..db.nrows())
+        .into_par_iter()
+        .map(|i| {
+            let mut sum = Zq::default();
+            for j in 0..db.ncols() {
+                sum = sum.mul_add(db_zq[(i, j)], query_zq[j], &modulus);
+            }
+            sum.to_bigint()
+        })
+        .collect();
+    DVector::from_vec(result)
+}
+
+/// Batched form of [`process_query`]: `queries` stacks several query vectors as columns and
+/// the database is scanned once to compute `db * queries`, rather than once per column.
+pub fn process_query_matrix(
+    db: &DMatrix<BigInt>,
+    queries: &DMatrix<BigInt>,
+    q: BigInt,
+) -> DMatrix<BigInt> {
+    let modulus = Modulus::from_bigint(&q);
+    let db_zq = db.map(|x| Zq::from_bigint(&x, &modulus));
+    let queries_zq = queries.map(|x| Zq::from_bigint(&x, &modulus));
+
+    // Same row-independence argument as `process_query`, generalized to a batch of columns.
+    let flat: Vec<BigInt> = (0..db.nrows())
+        .into_par_iter()
+        .flat_map(|i| {
+            (0..queries.ncols())
+                .map(|c| {
+                    let mut sum = Zq::default();
+                    for j in 0..db.ncols() {
+                        sum = sum.mul_add(db_zq[(i, j)], queries_zq[(j, c)], &modulus);
+                    }
+                    sum.to_bigint()
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    DMatrix::from_row_slice(db.nrows(), queries.ncols(), &flat)
+}
+
+/// Batched form of [`generate_query`]: encrypts each `v` in `vs` under its own fresh secret
+/// and error, returning the secrets and queries as columns of two matrices so the whole
+/// batch can be sent to [`process_query_matrix`] in a single round-trip.
+pub fn generate_query_batch(
+    params: &SimplePIRParams,
+    vs: &[DVector<BigInt>],
+    a: &DMatrix<BigInt>,
+) -> (DMatrix<BigInt>, DMatrix<BigInt>) {
+    let mut secrets = DMatrix::zeros(params.n, vs.len());
+    let mut queries = DMatrix::zeros(params.m, vs.len());
+    for (i, v) in vs.iter().enumerate() {
+        let (s, query) = generate_query(params, v, a);
+        secrets.set_column(i, &s);
+        queries.set_column(i, &query);
     }
-    result
+    (secrets, queries)
 }
 
 pub fn recover(
@@ -137,22 +414,126 @@ pub fn recover(
     answer: &DVector<BigInt>,
     params: &SimplePIRParams,
 ) -> DVector<BigInt> {
-    let modulus = &params.q.clone();
-    let delta = modulus / &params.p;
+    let modulus = &params.modulus;
+    let delta = &params.q / &params.p;
     let half_p: BigInt = &params.p >> 1;
 
-    let mut hint_s = DVector::zeros(answer.len());
+    let hint_zq = hint.map(|x| Zq::from_bigint(&x, modulus));
+    let s_zq: Vec<Zq> = s.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+    let answer_zq: Vec<Zq> = answer.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+
+    let mut decrypted = DVector::zeros(answer.len());
     for i in 0..answer.len() {
-        let mut sum = BigInt::zero();
+        let mut hint_s = Zq::default();
         for j in 0..s.len() {
-            sum = ((&sum + &hint[(i, j)] * &s[j]) % modulus + modulus) % modulus;
+            hint_s = hint_s.mul_add(hint_zq[(i, j)], s_zq[j], modulus);
         }
-        hint_s[i] = sum;
+        let diff = answer_zq[i].sub(hint_s, modulus).to_bigint();
+        let raw = &diff / &delta;
+        let centered = if raw >= half_p { raw - &params.p } else { raw };
+        decrypted[i] = centered;
     }
+    decrypted
+}
 
-    let mut decrypted = DVector::zeros(answer.len());
-    for i in 0..answer.len() {
-        let diff = ((&answer[i] + modulus - &hint_s[i]) % modulus + modulus) % modulus;
+/// Batched form of [`recover`]: `secrets` and `answers` carry one column per query (as
+/// produced by [`generate_query_batch`] / [`process_query_matrix`]) and each column is
+/// recovered against its own secret, sharing the single `hint` matrix.
+pub fn recover_batch(
+    hint: &DMatrix<BigInt>,
+    secrets: &DMatrix<BigInt>,
+    answers: &DMatrix<BigInt>,
+    params: &SimplePIRParams,
+) -> DMatrix<BigInt> {
+    assert_eq!(secrets.ncols(), answers.ncols(), "Secret/answer column count mismatch");
+
+    let mut result = DMatrix::zeros(answers.nrows(), answers.ncols());
+    for c in 0..answers.ncols() {
+        let recovered = recover(hint, &secrets.column(c).into_owned(), &answers.column(c).into_owned(), params);
+        result.set_column(c, &recovered);
+    }
+    result
+}
+
+/// DoublePIR-style hint compression: on top of the usual layer-one `hint`/`a` (as from
+/// [`gen_hint`]), treat `hint` itself as a database for a second SimplePIR layer against
+/// `params2`, producing `h2 = hint · a2`. `params2.m` must equal `params.n` (the hint's column
+/// count) so `a2` lines up with it. `hint` and `a` never leave the server: only `(h2, a2)` —
+/// or, after [`regenerate_matrix_a`], just `h2` plus `a2`'s seed — need to reach the client,
+/// which is the whole point when `hint` (size `db.nrows() x params.n`) would otherwise dominate
+/// the download for a large database. Use this instead of [`gen_hint`] only once the DB is
+/// large enough that shrinking the hint is worth the extra server-side matrix multiply;
+/// smaller databases should stick to the single-layer path.
+pub fn gen_hint_double(
+    params: &SimplePIRParams,
+    params2: &SimplePIRParams,
+    db: &DMatrix<BigInt>,
+) -> (DMatrix<BigInt>, DMatrix<BigInt>, DMatrix<BigInt>, DMatrix<BigInt>) {
+    let (hint, a) = gen_hint(params, db);
+    let (h2, a2) = gen_hint(params2, &hint);
+    (hint, a, h2, a2)
+}
+
+/// DoublePIR counterpart to [`generate_query`]: instead of a fresh layer-one secret drawn
+/// directly from `Z_q`, pick a smaller layer-two secret `s2` (length `params2.n`) and set
+/// `s1 = a2 · s2`. Because `s1` lives in `a2`'s column space, `hint · s1 == h2 · s2` exactly
+/// (matrix multiplication is associative mod `q`), so [`recover_double`] can reconstruct
+/// `hint · s1` from the much smaller `h2` instead of ever touching the full `hint`. The
+/// returned `query1` is encrypted exactly as [`generate_query`] would, so the server answers
+/// it with the ordinary [`process_query`] — only the client-side decode differs.
+pub fn generate_query_double(
+    params: &SimplePIRParams,
+    params2: &SimplePIRParams,
+    v: &DVector<BigInt>,
+    a: &DMatrix<BigInt>,
+    a2: &DMatrix<BigInt>,
+) -> (DVector<BigInt>, DVector<BigInt>, DVector<BigInt>) {
+    assert_eq!(v.len(), params.m, "Vector dimension mismatch");
+
+    let modulus = &params.modulus;
+    let s2 = gen_secret(params2.q.bits(), params2.n, None);
+
+    let a2_zq = a2.map(|x| Zq::from_bigint(&x, modulus));
+    let s2_zq: Vec<Zq> = s2.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+    let mut s1_data = Vec::with_capacity(a2.nrows());
+    for i in 0..a2.nrows() {
+        let mut sum = Zq::default();
+        for j in 0..a2.ncols() {
+            sum = sum.mul_add(a2_zq[(i, j)], s2_zq[j], modulus);
+        }
+        s1_data.push(sum.to_bigint());
+    }
+    let s1 = DVector::from_vec(s1_data);
+
+    let query1 = encrypt(params, v, a, &s1);
+    (s1, s2, query1)
+}
+
+/// DoublePIR counterpart to [`recover`]: peels the inner (second) layer first by recomputing
+/// `hint · s1` as `h2 · s2` (see [`generate_query_double`]), then finishes with the same
+/// delta/half_p centering [`recover`] uses for the outer (first) layer, just substituting the
+/// peeled value in place of an explicit `hint` matrix product.
+pub fn recover_double(
+    h2: &DMatrix<BigInt>,
+    s2: &DVector<BigInt>,
+    answer1: &DVector<BigInt>,
+    params: &SimplePIRParams,
+) -> DVector<BigInt> {
+    let modulus = &params.modulus;
+    let delta = &params.q / &params.p;
+    let half_p: BigInt = &params.p >> 1;
+
+    let h2_zq = h2.map(|x| Zq::from_bigint(&x, modulus));
+    let s2_zq: Vec<Zq> = s2.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+    let answer1_zq: Vec<Zq> = answer1.iter().map(|x| Zq::from_bigint(x, modulus)).collect();
+
+    let mut decrypted = DVector::zeros(answer1.len());
+    for i in 0..answer1.len() {
+        let mut hint_s1 = Zq::default();
+        for j in 0..s2.len() {
+            hint_s1 = hint_s1.mul_add(h2_zq[(i, j)], s2_zq[j], modulus);
+        }
+        let diff = answer1_zq[i].sub(hint_s1, modulus).to_bigint();
         let raw = &diff / &delta;
         let centered = if raw >= half_p { raw - &params.p } else { raw };
         decrypted[i] = centered;
@@ -292,4 +673,113 @@ mod tests {
         );
         println!("Success: Test passed!");
     }
+
+    #[test]
+    fn test_batch_retrieval() {
+        let matrix_height = 10;
+        let matrix_width = 10;
+        let max_val_bits = 12;
+        let k = 3;
+
+        let mut rng = rand::thread_rng();
+        let d_data: Vec<BigInt> = (0..matrix_height * matrix_width)
+            .map(|_| rng.gen_bigint(max_val_bits).abs())
+            .collect();
+        let d = DMatrix::from_vec(matrix_height, matrix_width, d_data);
+
+        let target_rows: Vec<usize> = (0..k).map(|_| rng.gen_range(0..matrix_width)).collect();
+        let queries: Vec<DVector<BigInt>> = target_rows
+            .iter()
+            .map(|&row| {
+                let mut v = DVector::<BigInt>::zeros(matrix_width);
+                v[row] = BigInt::one();
+                v
+            })
+            .collect();
+
+        let params = gen_params(matrix_height, 2048, 17);
+        let (hint, a) = gen_hint(&params, &d);
+        let (secrets, query_matrix) = generate_query_batch(&params, &queries, &a);
+        let answers = process_query_matrix(&d, &query_matrix, params.q.clone());
+        let results = recover_batch(&hint, &secrets, &answers, &params);
+
+        let tolerance = BigInt::from(10);
+        for (c, &row) in target_rows.iter().enumerate() {
+            let expected: Vec<BigInt> = (0..matrix_height).map(|i| d[(i, row)].clone()).collect();
+            for (i, e) in expected.iter().enumerate() {
+                let diff = (&results[(i, c)] - e).abs();
+                assert!(diff <= tolerance, "Batch column {} row {} out of tolerance", c, i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_double_pir_row_retrieval() {
+        let matrix_height = 10;
+        let matrix_width = 10;
+        let max_val_bits = 12;
+        let n = 2048;
+        let n2 = 256; // compressed hint width; much smaller than n
+
+        let mut rng = rand::thread_rng();
+        let d_data: Vec<BigInt> = (0..matrix_height * matrix_width)
+            .map(|_| rng.gen_bigint(max_val_bits).abs())
+            .collect();
+        let d = DMatrix::from_vec(matrix_height, matrix_width, d_data);
+
+        let target_row = rng.gen_range(0..matrix_width);
+        let mut v = DVector::<BigInt>::zeros(matrix_width);
+        v[target_row] = BigInt::one();
+
+        let expected: Vec<BigInt> = (0..matrix_height).map(|i| d[(i, target_row)].clone()).collect();
+
+        let params = gen_params(matrix_width, n, 17);
+        let params2 = gen_params(n, n2, 17); // params2.m must equal params.n
+
+        let (_hint, a, h2, a2) = gen_hint_double(&params, &params2, &d);
+        let (_s1, s2, query1) = generate_query_double(&params, &params2, &v, &a, &a2);
+        let answer1 = process_query(&d, &query1, params.q.clone());
+        let result = recover_double(&h2, &s2, &answer1, &params);
+
+        let tolerance = BigInt::from(10);
+        for (i, e) in expected.iter().enumerate() {
+            let diff = (&result[i] - e).abs();
+            assert!(diff <= tolerance, "Row {} out of tolerance", i);
+        }
+    }
+
+    #[test]
+    fn test_params_serde_roundtrip_preserves_a() {
+        let params = gen_params(10, 2048, 17);
+        let json = serde_json::to_string(&params).unwrap();
+        let decoded: SimplePIRParams = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.n, params.n);
+        assert_eq!(decoded.m, params.m);
+        assert_eq!(decoded.q, params.q);
+        assert_eq!(decoded.p, params.p);
+
+        // `a` is never sent over the wire; it must regenerate identically from the decoded
+        // params alone.
+        assert_eq!(regenerate_matrix_a(&params), regenerate_matrix_a(&decoded));
+    }
+
+    #[test]
+    fn test_barrett_reduce_non_power_of_two_modulus() {
+        // A modulus that doesn't divide `2^128`, so `Barrett::new`'s `mu` must be computed
+        // exactly rather than via the old `(u128::MAX / q) + 1` overestimate.
+        let q: u64 = (1u64 << 63) + 1;
+        let barrett = Barrett::new(q);
+
+        let mut rng = rand::thread_rng();
+        let q128 = q as u128;
+        let samples = [q128 - 1, q128, q128 + 1, q128 * q128 - 1]
+            .into_iter()
+            .chain((0..100).map(|_| rng.gen_range(0..q128 * q128)));
+
+        for x in samples {
+            let expected = (x % q as u128) as u64;
+            assert_eq!(barrett.reduce(x), expected, "mismatch reducing {} mod {}", x, q);
+        }
+    }
 }
@@ -1,14 +1,169 @@
-use ark_ff::Field;
-use rand::Rng;
+use ark_ff::{BigInteger, PrimeField};
+use num_bigint::BigUint;
 
-use crate::dtypes::Vector;
+use crate::dtypes::{Matrix, Vector};
 
-pub fn generate_s<F: Field>(dim: usize) -> Vector<F> {
-    let mut rng = rand::thread_rng();
-    Vector::new((0..dim).map(|_| F::rand(&mut rng)).collect())
+#[derive(Debug, Clone)]
+pub struct Params<Fp: PrimeField> {
+    pub a: Matrix<Fp>,  // A matrix
+    pub p: BigUint,     // Plaintext modulus (arbitrary width, not limited to 64 bits)
+    pub n: usize,       // LWE secret length
+    pub m: usize,       // Number of samples
+    pub stdev: f64,     // Standard deviation
 }
 
-pub fn generate_e<F: Field>(dim: usize, theta: f64) -> Vector<F> {
-    let mut rng = rand::thread_rng();
-    Vector::new((0..dim).map(|_| F::from(discrete_gaussian::sample_vartime(theta, &mut rng))).collect())
+pub fn generate_example_params<Fp: PrimeField>(n: usize, m: usize, stdev: f64) -> Params<Fp> {
+    generate_params(n, m, stdev, BigUint::from(2u8))
+}
+
+pub fn generate_params<Fp: PrimeField>(n: usize, m: usize, stdev: f64, p: BigUint) -> Params<Fp> {
+    let a = Matrix::from_random(m, n);
+    Params { a, p, n, m, stdev }
+}
+
+/// The full-width ciphertext modulus `q`, i.e. the field's prime, as a `BigUint`.
+pub(crate) fn field_modulus<Fp: PrimeField>() -> BigUint {
+    BigUint::from_bytes_le(&Fp::MODULUS.to_bytes_le())
+}
+
+/// `floor(q / p)`, computed on the full-width modulus rather than a 64-bit truncation of it.
+fn get_q_over_p<Fp: PrimeField>(params: &Params<Fp>) -> BigUint {
+    field_modulus::<Fp>() / &params.p
+}
+
+/// Lift a field element to its full-width unsigned integer representative in `[0, q)`.
+pub(crate) fn to_biguint<Fp: PrimeField>(x: Fp) -> BigUint {
+    BigUint::from_bytes_le(&x.into_bigint().to_bytes_le())
+}
+
+/// Round `noised` (an element of `Z_q`) down to its plaintext symbol via centered rounding:
+/// `round(p * noised / q) mod p`, carried out entirely in `BigUint` arithmetic so it stays
+/// correct regardless of how wide `q` is.
+fn round_to_plaintext<Fp: PrimeField>(noised: Fp, p: &BigUint) -> BigUint {
+    let q = field_modulus::<Fp>();
+    let noised = to_biguint::<Fp>(noised);
+    let numerator = noised * p;
+    let rounded = (numerator + &q / 2u8) / &q;
+    rounded % p
+}
+
+pub fn encrypt<Fp: PrimeField>(
+    params: &Params<Fp>,
+    secret: &Vector<Fp>,
+    e: &Vector<Fp>,
+    plaintext: Vector<Fp>,
+) -> (Matrix<Fp>, Vector<Fp>) {
+    // Check that the secret has the correct length
+    assert_eq!(secret.len(), params.n, "Secret length must match params.n");
+
+    // Check that the error vector has correct length
+    assert_eq!(e.len(), params.m, "Error vector length must match params.m");
+
+    // Check that plaintext is within range of plaintext modulus
+    assert!(plaintext.len() == params.m, "Plaintext length must match params.m");
+
+    let a_s = params.a.mul_vec(secret);
+    let b = &a_s + e;
+    let delta = Fp::from_le_bytes_mod_order(&get_q_over_p(params).to_bytes_le());
+    let c = &b + &plaintext.mul_scalar(delta);
+    (params.a.clone(), c)
+}
+
+pub fn decrypt<Fp: PrimeField>(
+    params: &Params<Fp>,
+    secret: &Vector<Fp>,
+    hint: &Matrix<Fp>,
+    ciphertext: Vector<Fp>,
+    index: usize,
+) -> BigUint {
+    assert!(secret.len() == params.n, "Secret length must match params.n");
+    assert!(ciphertext.len() == params.m, "Ciphertext length must match params.m");
+
+    let a_s = hint.mul_vec(secret);
+    let c_minus_a_s = &ciphertext - &a_s;
+
+    round_to_plaintext(c_minus_a_s[index], &params.p)
+}
+
+/// Encrypt a vector of plaintext symbols, one per ciphertext coordinate. This is the same
+/// math as [`encrypt`] (which is already vectorized over `plaintext`), named to pair with
+/// [`decrypt_vec`] for callers that want to round-trip a whole symbol vector at once.
+pub fn encrypt_vec<Fp: PrimeField>(
+    params: &Params<Fp>,
+    secret: &Vector<Fp>,
+    e: &Vector<Fp>,
+    plaintext: Vector<Fp>,
+) -> (Matrix<Fp>, Vector<Fp>) {
+    encrypt(params, secret, e, plaintext)
+}
+
+/// Decrypt every coordinate of `ciphertext`, returning one centered-rounded plaintext
+/// symbol (in `[0, p)`) per coordinate, computed in full `BigUint` width.
+pub fn decrypt_vec<Fp: PrimeField>(
+    params: &Params<Fp>,
+    secret: &Vector<Fp>,
+    hint: &Matrix<Fp>,
+    ciphertext: Vector<Fp>,
+) -> Vec<BigUint> {
+    assert!(secret.len() == params.n, "Secret length must match params.n");
+    assert!(ciphertext.len() == params.m, "Ciphertext length must match params.m");
+
+    let a_s = hint.mul_vec(secret);
+    let c_minus_a_s = &ciphertext - &a_s;
+
+    (0..ciphertext.len())
+        .map(|i| round_to_plaintext(c_minus_a_s[i], &params.p))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{generate_e, generate_s};
+    use ark_bn254::Fr;
+    use num_traits::ToPrimitive;
+    use rand::Rng;
+
+    fn random_plaintext(m: usize, p: &BigUint) -> (Vec<BigUint>, Vector<Fr>) {
+        let mut rng = rand::thread_rng();
+        let p_u64 = p.to_u64().unwrap();
+        let symbols: Vec<BigUint> = (0..m).map(|_| BigUint::from(rng.gen_range(0..p_u64))).collect();
+        let plaintext = Vector::new(symbols.iter().map(|s| Fr::from(s.to_u64().unwrap())).collect());
+        (symbols, plaintext)
+    }
+
+    fn roundtrip_for_p(p: u64) {
+        let (n, m) = (8, 16);
+        let p = BigUint::from(p);
+        let params = generate_params::<Fr>(n, m, 3.2, p.clone());
+
+        let secret = generate_s::<Fr>(n);
+        let e = generate_e::<Fr>(m, params.stdev);
+
+        let (symbols, plaintext) = random_plaintext(m, &p);
+        let (a, ciphertext) = encrypt_vec(&params, &secret, &e, plaintext);
+
+        // `decrypt_vec` expects the same `hint` shape `decrypt` does: the public matrix
+        // used to mask the secret (here just `a`, since there is no separate server hint
+        // in this single-party scheme).
+        let decrypted = decrypt_vec(&params, &secret, &a, ciphertext);
+        for (expected, actual) in symbols.iter().zip(decrypted.iter()) {
+            assert_eq!(expected, actual);
+        }
+    }
+
+    #[test]
+    fn roundtrip_p2() {
+        roundtrip_for_p(2);
+    }
+
+    #[test]
+    fn roundtrip_p16() {
+        roundtrip_for_p(16);
+    }
+
+    #[test]
+    fn roundtrip_p256() {
+        roundtrip_for_p(256);
+    }
 }
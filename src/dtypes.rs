@@ -105,6 +105,16 @@ impl<F: Field> Matrix<F> {
         result
     }
 
+    pub fn transpose(&self) -> Self {
+        let mut data = vec![vec![F::zero(); self.rows()]; self.cols()];
+        for i in 0..self.rows() {
+            for j in 0..self.cols() {
+                data[j][i] = self.data[i][j];
+            }
+        }
+        Self { data }
+    }
+
     #[inline]
     pub fn rows(&self) -> usize {
         self.data.len()
@@ -216,6 +226,10 @@ impl<F: Field> Vector<F> {
     pub fn len(&self) -> usize {
         self.data.len()
     }
+
+    pub fn into_vec(self) -> Vec<F> {
+        self.data
+    }
 }
 
 impl<F: Field> std::ops::Index<usize> for Vector<F> {
@@ -0,0 +1,176 @@
+//! A SimplePIR-style backend built directly on the generic [`Matrix`]/[`Vector`] types over
+//! `ark_ff::PrimeField`, instead of the `nalgebra`/`num_bigint::BigInt` stack `simplepir` uses.
+//! Reducing mod a fixed field prime lets every operation run through `ark_ff`'s constant-time
+//! Montgomery arithmetic rather than arbitrary-precision `BigInt`, at the cost of fixing `q` to
+//! one prime rather than an arbitrary power of two. The public matrix `A`, the database hint,
+//! and query/answer vectors all reuse [`crate::crypto::encrypt`]/[`field_modulus`]/[`to_biguint`]
+//! rather than re-deriving that math.
+//!
+//! Wiring this in as an alternative to `tiptoe-rs`'s `nalgebra`-based `Database` trait is future
+//! work: that trait lives in a separate crate built entirely around `DMatrix<BigInt>`, with no
+//! dependency on this one, so "select between backends" is left as the natural next step once
+//! the two crates share a boundary.
+
+use ark_ff::{Fp64, MontBackend, MontConfig, PrimeField, UniformRand};
+use num_bigint::{BigInt, BigUint};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+
+use crate::crypto::{field_modulus, to_biguint};
+use crate::dtypes::{Matrix, Vector};
+
+/// The Goldilocks prime `2^64 - 2^32 + 1`, a 64-bit-ish field modulus well suited to fast
+/// modular reduction, used as this backend's ciphertext modulus `q`.
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct FqConfig;
+
+/// The field element type every matrix/vector in this module is built from.
+pub type Fq = Fp64<MontBackend<FqConfig, 1>>;
+
+#[derive(Debug, Clone)]
+pub struct PIRParams {
+    pub n: usize,      // LWE secret length
+    pub m: usize,      // Number of samples / database row width
+    pub p: BigUint,    // Plaintext modulus
+    pub stdev: f64,    // Error distribution standard deviation
+    pub seed: u64,     // Seed `A` is derived from, so it never needs to be transmitted
+}
+
+/// Analogous to `simplepir::gen_params`: picks a fresh seed for `A` and otherwise just
+/// records the caller's dimensions/moduli.
+pub fn gen_params(m: usize, n: usize, p: BigUint, stdev: f64) -> PIRParams {
+    PIRParams {
+        n,
+        m,
+        p,
+        stdev,
+        seed: rand::thread_rng().gen(),
+    }
+}
+
+/// Deterministically regenerate the public matrix `A` (shape `m x n`) from `seed` alone, so
+/// a holder of [`PIRParams`] never needs to receive `A` over the wire.
+pub fn gen_matrix_a(seed: u64, m: usize, n: usize) -> Matrix<Fq> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    Matrix::new(
+        (0..m)
+            .map(|_| (0..n).map(|_| Fq::rand(&mut rng)).collect())
+            .collect(),
+    )
+}
+
+/// Build the database hint `hint = db * A`, one row at a time via [`Matrix::mul_vec`] against
+/// `A`'s transpose (`hint[i] = A^T * db[i]`), rather than a single fused matrix product.
+pub fn gen_hint(db: &Matrix<Fq>, a: &Matrix<Fq>) -> Matrix<Fq> {
+    let a_t = a.transpose();
+    let rows: Vec<Vec<Fq>> = db
+        .data
+        .iter()
+        .map(|row| a_t.mul_vec(&Vector::new(row.clone())).into_vec())
+        .collect();
+    Matrix::new(rows)
+}
+
+/// Encrypt the client's one-hot selection vector `v` against `A`, returning the secret `s`
+/// alongside the query ciphertext. This is [`crate::crypto::encrypt`] under the hood (so the
+/// LWE math itself isn't duplicated here); the only new part is generating `s`/the error
+/// vector for a database-query-shaped call, matching `simplepir::generate_query`.
+pub fn generate_query(params: &PIRParams, v: &Vector<Fq>, a: &Matrix<Fq>) -> (Vector<Fq>, Vector<Fq>) {
+    assert_eq!(v.len(), params.m, "Vector dimension mismatch");
+
+    let mut rng = rand::thread_rng();
+    let s = Vector::new((0..params.n).map(|_| Fq::rand(&mut rng)).collect());
+
+    let normal = Normal::new(0.0, params.stdev).unwrap();
+    let e = Vector::new(
+        (0..params.m)
+            .map(|_| Fq::from(normal.sample(&mut rng).round() as i64))
+            .collect(),
+    );
+
+    let crypto_params = crate::crypto::Params {
+        a: a.clone(),
+        p: params.p.clone(),
+        n: params.n,
+        m: params.m,
+        stdev: params.stdev,
+    };
+    let (_, query) = crate::crypto::encrypt(&crypto_params, &s, &e, v.clone());
+
+    (s, query)
+}
+
+/// The server side of a query: scan the database against the query vector, never touching
+/// the secret. Just `db`'s own [`Matrix::mul_vec`] — no separate implementation needed.
+pub fn process_query(db: &Matrix<Fq>, query: &Vector<Fq>) -> Vector<Fq> {
+    db.mul_vec(query)
+}
+
+/// Recover the row of database values the client queried for: round `diff[i]` to the nearest
+/// multiple of `delta` and fold the quotient back into `[0, p)`, rather than floor-dividing and
+/// centering the quotient against `half_p` (which misdecodes any plaintext symbol `>= p/2`, since
+/// `delta * p/2 >= q/2` already looks "negative" under that scheme). Rounding instead of
+/// flooring also means a negative-noise term that wraps `diff[i]` up near `q` rounds to `p`,
+/// which folds back to the correct symbol instead of landing one low.
+pub fn recover(hint: &Matrix<Fq>, s: &Vector<Fq>, answer: &Vector<Fq>, p: &BigUint) -> Vec<BigInt> {
+    let q = field_modulus::<Fq>();
+    let delta = &q / p;
+    let half_delta = &delta / 2u8;
+
+    let a_s = hint.mul_vec(s);
+    let diff = answer.sub(&a_s);
+
+    (0..diff.len())
+        .map(|i| {
+            let raw = to_biguint::<Fq>(diff[i]);
+            let rounded = (&raw + &half_delta) / &delta;
+            BigInt::from(rounded % p)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::One;
+    use num_bigint::BigInt as SignedBigInt;
+
+    #[test]
+    fn test_row_retrieval() {
+        let matrix_height = 10;
+        let matrix_width = 10;
+        let n = 64;
+
+        let mut rng = rand::thread_rng();
+        let db_data: Vec<Vec<Fq>> = (0..matrix_height)
+            .map(|_| {
+                (0..matrix_width)
+                    .map(|_| Fq::from(rng.gen_range(0..1000u64)))
+                    .collect()
+            })
+            .collect();
+        let db = Matrix::new(db_data.clone());
+
+        let target_col = rng.gen_range(0..matrix_width);
+        let mut v_data = vec![Fq::from(0u64); matrix_width];
+        v_data[target_col] = Fq::one();
+        let v = Vector::new(v_data);
+
+        let params = gen_params(matrix_width, n, BigUint::from(1000u32), 3.2);
+        let a = gen_matrix_a(params.seed, params.m, params.n);
+        let hint = gen_hint(&db, &a);
+
+        let (s, query) = generate_query(&params, &v, &a);
+        let answer = process_query(&db, &query);
+        let result = recover(&hint, &s, &answer, &params.p);
+
+        let tolerance = SignedBigInt::from(5);
+        for row in 0..matrix_height {
+            let expected = to_biguint::<Fq>(db_data[row][target_col]);
+            let diff = (&result[row] - SignedBigInt::from(expected)).abs();
+            assert!(diff <= tolerance, "row {} out of tolerance", row);
+        }
+    }
+}
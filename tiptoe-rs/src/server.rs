@@ -14,9 +14,92 @@ pub trait Database {
         Self: Sized;
     fn update(&mut self) -> Result<()>;
     fn respond(&self, query: &DVector<BigInt>) -> Result<DVector<BigInt>>;
+    /// Batched form of [`Database::respond`]: `queries` stacks several query vectors as
+    /// columns, treating `respond` as the single-column case, and the database is scanned
+    /// once to answer all of them.
+    fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>>;
     fn params(&self) -> &SimplePIRParams;
     fn hint(&self) -> &DMatrix<BigInt>;
     fn a(&self) -> &DMatrix<BigInt>;
+    /// Monotonically increasing generation counter, bumped every time the underlying data
+    /// (and therefore `hint`/`a`/`params`) is rebuilt. Lets clients detect that their cached
+    /// copies are stale without re-downloading anything.
+    fn version(&self) -> u64;
+
+    /// Number of independent SimplePIR shards backing this database. A plain, single-matrix
+    /// database (the overwhelming majority) is the `N = 1` case, and the default methods
+    /// below simply forward to the unsharded accessors above; only [`ShardedDatabase`]
+    /// (and types built on it) override them for real.
+    fn num_shards(&self) -> usize {
+        1
+    }
+
+    fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.respond(query)
+    }
+
+    fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.respond_batch(queries)
+    }
+
+    fn shard_params(&self, shard: usize) -> &SimplePIRParams {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.params()
+    }
+
+    fn shard_hint(&self, shard: usize) -> &DMatrix<BigInt> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.hint()
+    }
+
+    fn shard_a(&self, shard: usize) -> &DMatrix<BigInt> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.a()
+    }
+
+    /// The DoublePIR-compressed hint's own params/`H2`/`A2` (see
+    /// [`simplepir::gen_hint_double`]), present only once a database has opted into
+    /// double-hint compression via [`SimplePirDatabase::with_double_pir`]. `None` means the
+    /// database only ever serves the plain single-layer `hint`/`a` above.
+    ///
+    /// Nothing downstream consumes these yet: `network::AsyncDatabase`/`RemoteDatabase` don't
+    /// expose RPCs for them, and `tiptoe::client::Client` always fetches the plain `hint`/`a`
+    /// and calls `simplepir::recover`. Enabling `with_double_pir` today only shrinks what the
+    /// server *could* serve, not what it does serve. Wiring a client decode path means adding
+    /// those RPCs plus a `recover_double` branch wherever `DbSnapshot` is used — left for when a
+    /// database is actually large enough that the plain hint's download size is worth it.
+    fn double_params(&self) -> Option<&SimplePIRParams> {
+        None
+    }
+
+    fn hint2(&self) -> Option<&DMatrix<BigInt>> {
+        None
+    }
+
+    fn a2(&self) -> Option<&DMatrix<BigInt>> {
+        None
+    }
+
+    fn shard_double_params(&self, shard: usize) -> Option<&SimplePIRParams> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.double_params()
+    }
+
+    fn shard_hint2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.hint2()
+    }
+
+    fn shard_a2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded database");
+        self.a2()
+    }
 }
 
 pub struct SimplePirDatabase {
@@ -24,6 +107,16 @@ pub struct SimplePirDatabase {
     data: DMatrix<BigInt>,
     hint: Option<DMatrix<BigInt>>,
     a: Option<DMatrix<BigInt>>,
+    version: u64,
+    /// Worker threads to use for `gen_hint`/`process_query`'s row-parallel scans. `None`
+    /// (the default) just runs them on rayon's global pool.
+    num_threads: Option<usize>,
+    /// `(n2, mod_power2)` for the DoublePIR hint-of-hint layer, set via
+    /// [`Self::with_double_pir`]. `None` (the default) keeps the single-layer `hint`/`a` path.
+    double_pir: Option<(usize, u32)>,
+    double_params: Option<SimplePIRParams>,
+    hint2: Option<DMatrix<BigInt>>,
+    a2: Option<DMatrix<BigInt>>,
 }
 
 impl SimplePirDatabase {
@@ -33,18 +126,78 @@ impl SimplePirDatabase {
             params: None,
             hint: None,
             a: None,
+            version: 0,
+            num_threads: None,
+            double_pir: None,
+            double_params: None,
+            hint2: None,
+            a2: None,
+        }
+    }
+
+    /// Cap the number of worker threads used to rebuild the hint and answer queries, instead
+    /// of rayon's global pool default (one per core).
+    pub fn with_num_threads(mut self, num_threads: usize) -> Self {
+        self.num_threads = Some(num_threads);
+        self
+    }
+
+    /// Opt into DoublePIR-style hint compression (see [`gen_hint_double`]): instead of serving
+    /// the full `hint` (size `db.nrows() x params.n`), also build a second layer compressing it
+    /// down to `hint2` (size `db.nrows() x n2`) plus its own `a2`, which is what clients should
+    /// download once the plain `hint` would be too large to be worth shipping. `mod_power2` is
+    /// the modulus-bit-width argument passed through to the second layer's `gen_params`.
+    ///
+    /// No client in this crate downloads `hint2`/`a2` yet (see the `Database` trait's doc
+    /// comment on [`Database::double_params`]) — this only prepares the compressed hint server-side.
+    pub fn with_double_pir(mut self, n2: usize, mod_power2: u32) -> Self {
+        self.double_pir = Some((n2, mod_power2));
+        self
+    }
+
+    /// Run `f` on this database's own thread pool if `num_threads` was set, otherwise on
+    /// rayon's default global pool.
+    fn on_thread_pool<T: Send>(&self, f: impl FnOnce() -> T + Send) -> T {
+        match self.num_threads {
+            Some(n) => rayon::ThreadPoolBuilder::new()
+                .num_threads(n)
+                .build()
+                .expect("failed to build SimplePirDatabase thread pool")
+                .install(f),
+            None => f(),
         }
     }
 
     pub fn update_db(&mut self, data: DMatrix<BigInt>) -> Result<()> {
         self.data = data;
 
-        let params = gen_params(self.data.nrows(), self.data.ncols(), 64);
-        let (hint, a) = gen_hint(&params, &self.data);
+        // `gen_params`'s first argument becomes `params.m`, which `gen_hint`/`process_query`
+        // require to equal `db.ncols()` (the query ciphertext is indexed by column, answered
+        // one row at a time) — so it must be derived from `self.data.ncols()` regardless of
+        // which axis a sharded caller partitioned records along, not `self.data.nrows()`.
+        // Using `nrows` here happened to work only because the unsharded database was square.
+        let params = gen_params(self.data.ncols(), self.data.ncols(), 64);
+
+        match self.double_pir {
+            Some((n2, mod_power2)) => {
+                let params2 = gen_params(params.n, n2, mod_power2);
+                let (hint, a, hint2, a2) =
+                    self.on_thread_pool(|| gen_hint_double(&params, &params2, &self.data));
+                self.double_params = Some(params2);
+                self.hint = Some(hint);
+                self.a = Some(a);
+                self.hint2 = Some(hint2);
+                self.a2 = Some(a2);
+            }
+            None => {
+                let (hint, a) = self.on_thread_pool(|| gen_hint(&params, &self.data));
+                self.hint = Some(hint);
+                self.a = Some(a);
+            }
+        }
 
         self.params = Some(params);
-        self.hint = Some(hint);
-        self.a = Some(a);
+        self.version = self.version.wrapping_add(1);
 
         Ok(())
     }
@@ -54,10 +207,20 @@ impl SimplePirDatabase {
             .params
             .as_ref()
             .ok_or_else(|| PirError::Database("Database not initialized".to_string()))?;
-        let answer = process_query(&self.data, query, params.q.clone());
+        let answer = self.on_thread_pool(|| process_query(&self.data, query, params.q.clone()));
         Ok(answer)
     }
 
+    pub fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>> {
+        let params = self
+            .params
+            .as_ref()
+            .ok_or_else(|| PirError::Database("Database not initialized".to_string()))?;
+        let answers =
+            self.on_thread_pool(|| process_query_matrix(&self.data, queries, params.q.clone()));
+        Ok(answers)
+    }
+
     fn params(&self) -> &SimplePIRParams {
         self.params
             .as_ref()
@@ -78,17 +241,223 @@ impl SimplePirDatabase {
             .ok_or(PirError::Database("Database not initialized".to_string()))
             .unwrap()
     }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn double_params(&self) -> Option<&SimplePIRParams> {
+        self.double_params.as_ref()
+    }
+
+    fn hint2(&self) -> Option<&DMatrix<BigInt>> {
+        self.hint2.as_ref()
+    }
+
+    fn a2(&self) -> Option<&DMatrix<BigInt>> {
+        self.a2.as_ref()
+    }
+}
+
+/// Which axis of the input matrix [`ShardedDatabase::update_db`] partitions across shards.
+/// Records sit on the row axis for an embedding-style database (each row is one record's
+/// score against the query), but on the *column* axis for an encoding-style database (see
+/// `utils::encode_data`, which writes record `i`'s bytes down column `i`), so the two
+/// databases need to be sharded along different axes even though they share one record count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShardAxis {
+    Rows,
+    Columns,
+}
+
+/// A database sharded across `N` independent [`SimplePirDatabase`] instances, each holding
+/// its own `hint`/`A`. Records are split into contiguous chunks of `ceil(records / N)`, so the
+/// last shard holds whatever is left over (possibly fewer records, or none), and a corpus can
+/// grow past one square matrix's dimension instead of being truncated to it. This type
+/// overrides the `Database` trait's shard methods; with `N = 1` it behaves like a plain
+/// single-matrix database.
+pub struct ShardedDatabase {
+    shards: Vec<SimplePirDatabase>,
+    axis: ShardAxis,
+    version: u64,
+}
+
+impl ShardedDatabase {
+    pub fn new(num_shards: usize, axis: ShardAxis) -> Self {
+        assert!(num_shards >= 1, "ShardedDatabase needs at least one shard");
+        Self {
+            shards: (0..num_shards)
+                .map(|_| SimplePirDatabase::new(DMatrix::zeros(1, 1)))
+                .collect(),
+            axis,
+            version: 0,
+        }
+    }
+
+    /// Partition `data`'s records along `self.axis` into contiguous chunks of
+    /// `ceil(records / N)` (the last shard gets what is left, possibly fewer; a shard left with
+    /// no records gets a single all-zero row or column) and rebuild each shard's own `hint`/`A`
+    /// from its slice.
+    pub fn update_db(&mut self, data: DMatrix<BigInt>) -> Result<()> {
+        let num_shards = self.shards.len();
+        let num_records = match self.axis {
+            ShardAxis::Rows => data.nrows(),
+            ShardAxis::Columns => data.ncols(),
+        };
+        let records_per_shard = num_records.div_ceil(num_shards);
+
+        for (i, shard) in self.shards.iter_mut().enumerate() {
+            let start = (i * records_per_shard).min(num_records);
+            let end = ((i + 1) * records_per_shard).min(num_records);
+            let slice = if start < end {
+                match self.axis {
+                    ShardAxis::Rows => data.rows(start, end - start).into_owned(),
+                    ShardAxis::Columns => data.columns(start, end - start).into_owned(),
+                }
+            } else {
+                match self.axis {
+                    ShardAxis::Rows => DMatrix::zeros(1, data.ncols().max(1)),
+                    ShardAxis::Columns => DMatrix::zeros(data.nrows().max(1), 1),
+                }
+            };
+            shard.update_db(slice)?;
+        }
+
+        self.version = self.version.wrapping_add(1);
+        Ok(())
+    }
+
+    /// Cap the worker threads each shard uses for its own `gen_hint`/`process_query` scans.
+    /// See [`SimplePirDatabase::with_num_threads`].
+    pub fn with_num_threads(mut self, num_threads: usize) -> Self {
+        self.shards = self
+            .shards
+            .into_iter()
+            .map(|shard| shard.with_num_threads(num_threads))
+            .collect();
+        self
+    }
+
+    /// Opt every shard into DoublePIR-style hint compression. See
+    /// [`SimplePirDatabase::with_double_pir`].
+    pub fn with_double_pir(mut self, n2: usize, mod_power2: u32) -> Self {
+        self.shards = self
+            .shards
+            .into_iter()
+            .map(|shard| shard.with_double_pir(n2, mod_power2))
+            .collect();
+        self
+    }
+}
+
+impl Database for ShardedDatabase {
+    fn new() -> Result<Self> {
+        Ok(Self::new(1, ShardAxis::Rows))
+    }
+
+    fn update(&mut self) -> Result<()> {
+        Err(PirError::Database(
+            "ShardedDatabase has no data source of its own; call update_db directly".to_string(),
+        )
+        .into())
+    }
+
+    fn respond(&self, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        self.shard_respond(0, query)
+    }
+
+    fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>> {
+        self.shards[0].respond_batch(queries)
+    }
+
+    fn params(&self) -> &SimplePIRParams {
+        self.shard_params(0)
+    }
+
+    fn hint(&self) -> &DMatrix<BigInt> {
+        self.shard_hint(0)
+    }
+
+    fn a(&self) -> &DMatrix<BigInt> {
+        self.shard_a(0)
+    }
+
+    fn double_params(&self) -> Option<&SimplePIRParams> {
+        self.shard_double_params(0)
+    }
+
+    fn hint2(&self) -> Option<&DMatrix<BigInt>> {
+        self.shard_hint2(0)
+    }
+
+    fn a2(&self) -> Option<&DMatrix<BigInt>> {
+        self.shard_a2(0)
+    }
+
+    fn version(&self) -> u64 {
+        self.version
+    }
+
+    fn num_shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        self.shards[shard].respond(query)
+    }
+
+    fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        self.shards[shard].respond_batch(queries)
+    }
+
+    fn shard_params(&self, shard: usize) -> &SimplePIRParams {
+        self.shards[shard].params()
+    }
+
+    fn shard_hint(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.shards[shard].hint()
+    }
+
+    fn shard_a(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.shards[shard].a()
+    }
+
+    fn shard_double_params(&self, shard: usize) -> Option<&SimplePIRParams> {
+        self.shards[shard].double_params()
+    }
+
+    fn shard_hint2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.shards[shard].hint2()
+    }
+
+    fn shard_a2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.shards[shard].a2()
+    }
 }
 
 pub struct EmbeddingDatabase {
-    db: SimplePirDatabase,
+    db: ShardedDatabase,
     embedder: BertEmbedder,
 }
 
+impl EmbeddingDatabase {
+    /// Opt into an `N`-shard embedding index instead of the default single matrix.
+    pub fn new_sharded(num_shards: usize) -> Result<Self> {
+        Ok(Self {
+            db: ShardedDatabase::new(num_shards, ShardAxis::Rows),
+            embedder: BertEmbedder::new().map_err(|e| PirError::Embedding(e.to_string()))?,
+        })
+    }
+}
+
 impl Database for EmbeddingDatabase {
     fn new() -> Result<Self> {
         Ok(Self {
-            db: SimplePirDatabase::new(DMatrix::zeros(1, 1)),
+            db: ShardedDatabase::new(1, ShardAxis::Rows),
             embedder: BertEmbedder::new().map_err(|e| PirError::Embedding(e.to_string()))?,
         })
     }
@@ -138,6 +507,14 @@ impl Database for EmbeddingDatabase {
         self.db.respond(query)
     }
 
+    fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>> {
+        self.db.respond_batch(queries)
+    }
+
+    fn version(&self) -> u64 {
+        self.db.version()
+    }
+
     fn params(&self) -> &SimplePIRParams {
         self.db.params()
     }
@@ -149,16 +526,79 @@ impl Database for EmbeddingDatabase {
     fn a(&self) -> &DMatrix<BigInt> {
         self.db.a()
     }
+
+    fn double_params(&self) -> Option<&SimplePIRParams> {
+        self.db.double_params()
+    }
+
+    fn hint2(&self) -> Option<&DMatrix<BigInt>> {
+        self.db.hint2()
+    }
+
+    fn a2(&self) -> Option<&DMatrix<BigInt>> {
+        self.db.a2()
+    }
+
+    fn num_shards(&self) -> usize {
+        self.db.num_shards()
+    }
+
+    fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        self.db.shard_respond(shard, query)
+    }
+
+    fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        self.db.shard_respond_batch(shard, queries)
+    }
+
+    fn shard_params(&self, shard: usize) -> &SimplePIRParams {
+        self.db.shard_params(shard)
+    }
+
+    fn shard_hint(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.db.shard_hint(shard)
+    }
+
+    fn shard_a(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.db.shard_a(shard)
+    }
+
+    fn shard_double_params(&self, shard: usize) -> Option<&SimplePIRParams> {
+        self.db.shard_double_params(shard)
+    }
+
+    fn shard_hint2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.db.shard_hint2(shard)
+    }
+
+    fn shard_a2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.db.shard_a2(shard)
+    }
 }
 
 pub struct EncodingDatabase {
-    db: SimplePirDatabase,
+    db: ShardedDatabase,
+}
+
+impl EncodingDatabase {
+    /// Opt into an `N`-shard encoding store instead of the default single matrix. Callers
+    /// sharding the paired `EmbeddingDatabase` should use the same `num_shards` here, since
+    /// the client assumes both databases partition records identically.
+    pub fn new_sharded(num_shards: usize) -> Result<Self> {
+        Ok(Self {
+            db: ShardedDatabase::new(num_shards, ShardAxis::Columns),
+        })
+    }
 }
 
 impl Database for EncodingDatabase {
     fn new() -> Result<Self> {
         Ok(Self {
-            db: SimplePirDatabase::new(DMatrix::zeros(1, 1)),
+            db: ShardedDatabase::new(1, ShardAxis::Columns),
         })
     }
 
@@ -210,6 +650,14 @@ impl Database for EncodingDatabase {
         self.db.respond(query)
     }
 
+    fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>> {
+        self.db.respond_batch(queries)
+    }
+
+    fn version(&self) -> u64 {
+        self.db.version()
+    }
+
     fn params(&self) -> &SimplePIRParams {
         self.db.params()
     }
@@ -221,4 +669,56 @@ impl Database for EncodingDatabase {
     fn a(&self) -> &DMatrix<BigInt> {
         self.db.a()
     }
+
+    fn double_params(&self) -> Option<&SimplePIRParams> {
+        self.db.double_params()
+    }
+
+    fn hint2(&self) -> Option<&DMatrix<BigInt>> {
+        self.db.hint2()
+    }
+
+    fn a2(&self) -> Option<&DMatrix<BigInt>> {
+        self.db.a2()
+    }
+
+    fn num_shards(&self) -> usize {
+        self.db.num_shards()
+    }
+
+    fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        self.db.shard_respond(shard, query)
+    }
+
+    fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        self.db.shard_respond_batch(shard, queries)
+    }
+
+    fn shard_params(&self, shard: usize) -> &SimplePIRParams {
+        self.db.shard_params(shard)
+    }
+
+    fn shard_hint(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.db.shard_hint(shard)
+    }
+
+    fn shard_a(&self, shard: usize) -> &DMatrix<BigInt> {
+        self.db.shard_a(shard)
+    }
+
+    fn shard_double_params(&self, shard: usize) -> Option<&SimplePIRParams> {
+        self.db.shard_double_params(shard)
+    }
+
+    fn shard_hint2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.db.shard_hint2(shard)
+    }
+
+    fn shard_a2(&self, shard: usize) -> Option<&DMatrix<BigInt>> {
+        self.db.shard_a2(shard)
+    }
 }
@@ -1,9 +1,15 @@
 use anyhow::Result;
+use futures::future::try_join_all;
 use nalgebra::{DMatrix, DVector};
 use num_bigint::BigInt;
 use num_traits::One;
-use simplepir::{generate_query, recover, SimplePIRParams};
+use simplepir::{
+    generate_query, generate_query_batch, recover, recover_batch, regenerate_matrix_a,
+    SimplePIRParams,
+};
 use std::cmp::Ordering;
+use std::collections::HashMap;
+use tokio::sync::RwLock;
 
 use crate::{
     embedding::BertEmbedder,
@@ -12,6 +18,54 @@ use crate::{
     server::{Database, EmbeddingDatabase, EncodingDatabase},
 };
 
+/// A consistent snapshot of one shard's public material for one logical query: `params`,
+/// `a` and `hint`, tagged with the server-reported generation they were fetched at so a
+/// cached copy can be recognized as stale once the database is rebuilt.
+///
+/// Always the plain single-layer `hint`/`a`, never the DoublePIR-compressed `hint2`/`a2` a
+/// server may also have built via `SimplePirDatabase::with_double_pir` — this crate has no RPC
+/// to fetch those or branch on `simplepir::recover_double`, so a database opting into double-hint
+/// compression shrinks nothing a client here actually downloads. Worth adding once a database
+/// is large enough for that saving to matter.
+#[derive(Clone)]
+struct DbSnapshot {
+    version: u64,
+    params: SimplePIRParams,
+    a: DMatrix<BigInt>,
+    hint: DMatrix<BigInt>,
+}
+
+/// Return a snapshot of `shard` no older than the database's current generation: reuse the
+/// cached one if its `version` still matches, otherwise fetch `params`/`hint` for that shard
+/// concurrently and replace its cache entry. `a` is never fetched over the wire — it's
+/// regenerated locally from `params.seed`, since that's all `a` is a function of (see
+/// `simplepir::regenerate_matrix_a`). This is what lets a remote client notice a server-side
+/// rebuild instead of decoding against a stale `hint`. An unsharded database (`N = 1`) only
+/// ever has a `shard = 0` entry, so this degenerates to the old single-cache behavior.
+async fn fetch_shard_snapshot<T: Database>(
+    conn: &DatabaseConnection<T>,
+    cache: &RwLock<HashMap<usize, DbSnapshot>>,
+    shard: usize,
+) -> Result<DbSnapshot> {
+    let current_version = conn.version().await?;
+    if let Some(snapshot) = cache.read().await.get(&shard).cloned() {
+        if snapshot.version == current_version {
+            return Ok(snapshot);
+        }
+    }
+
+    let (params, hint) = tokio::try_join!(conn.shard_params(shard), conn.shard_hint(shard))?;
+    let a = regenerate_matrix_a(&params);
+    let snapshot = DbSnapshot {
+        version: current_version,
+        params,
+        a,
+        hint,
+    };
+    cache.write().await.insert(shard, snapshot.clone());
+    Ok(snapshot)
+}
+
 // Each database can be either local or remote
 pub enum DatabaseConnection<T> {
     Local(T),
@@ -25,37 +79,62 @@ impl<T: Database> DatabaseConnection<T> {
             Self::Local(db) => db
                 .update()
                 .map_err(|e| PirError::Database(format!("Update failed: {}", e)).into()),
+            // A remote server rebuilds and republishes its own database on a timer (see
+            // `network::run_server`); the client has nothing to trigger here. Picking up
+            // the new data is handled by `Client::update` re-checking `version()`, below.
             Self::Remote(_db) => Ok(()),
         }
     }
 
-    async fn respond(&self, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+    async fn version(&self) -> Result<u64> {
+        match self {
+            Self::Local(db) => Ok(db.version()),
+            Self::Remote(db) => db.get_version().await,
+        }
+    }
+
+    /// Number of independent SimplePIR shards backing this database. `1` for a plain
+    /// single-matrix database.
+    async fn num_shards(&self) -> Result<usize> {
+        match self {
+            Self::Local(db) => Ok(db.num_shards()),
+            Self::Remote(db) => db.get_num_shards().await,
+        }
+    }
+
+    async fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
         match self {
             Self::Local(db) => db
-                .respond(query)
+                .shard_respond(shard, query)
                 .map_err(|e| PirError::Database(format!("Response failed: {}", e)).into()),
-            Self::Remote(db) => db.respond(query).await,
+            Self::Remote(db) => db.shard_respond(shard, query).await,
         }
     }
 
-    async fn params(&self) -> Result<SimplePIRParams> {
+    async fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
         match self {
-            Self::Local(db) => Ok(db.params().clone()),
-            Self::Remote(db) => db.get_params().await,
+            Self::Local(db) => db
+                .shard_respond_batch(shard, queries)
+                .map_err(|e| PirError::Database(format!("Batch response failed: {}", e)).into()),
+            Self::Remote(db) => db.shard_respond_batch(shard, queries).await,
         }
     }
 
-    async fn hint(&self) -> Result<DMatrix<BigInt>> {
+    async fn shard_params(&self, shard: usize) -> Result<SimplePIRParams> {
         match self {
-            Self::Local(db) => Ok(db.hint().clone()),
-            Self::Remote(db) => db.get_hint().await,
+            Self::Local(db) => Ok(db.shard_params(shard).clone()),
+            Self::Remote(db) => db.shard_params(shard).await,
         }
     }
 
-    async fn a(&self) -> Result<DMatrix<BigInt>> {
+    async fn shard_hint(&self, shard: usize) -> Result<DMatrix<BigInt>> {
         match self {
-            Self::Local(db) => Ok(db.a().clone()),
-            Self::Remote(db) => db.get_a().await,
+            Self::Local(db) => Ok(db.shard_hint(shard).clone()),
+            Self::Remote(db) => db.shard_hint(shard).await,
         }
     }
 }
@@ -65,6 +144,8 @@ pub struct Client {
     embedding_db: DatabaseConnection<EmbeddingDatabase>,
     encoding_db: DatabaseConnection<EncodingDatabase>,
     embedder: BertEmbedder,
+    embedding_cache: RwLock<HashMap<usize, DbSnapshot>>,
+    encoding_cache: RwLock<HashMap<usize, DbSnapshot>>,
 }
 
 impl Client {
@@ -73,6 +154,21 @@ impl Client {
             embedding_db: DatabaseConnection::Local(EmbeddingDatabase::new()?),
             encoding_db: DatabaseConnection::Local(EncodingDatabase::new()?),
             embedder: BertEmbedder::new()?,
+            embedding_cache: RwLock::new(HashMap::new()),
+            encoding_cache: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Like [`Self::new_local`], but backed by an `N`-shard embedding/encoding pair instead of
+    /// the default single matrix. See [`EmbeddingDatabase::new_sharded`]/
+    /// [`EncodingDatabase::new_sharded`].
+    pub fn new_local_sharded(num_shards: usize) -> Result<Self> {
+        Ok(Self {
+            embedding_db: DatabaseConnection::Local(EmbeddingDatabase::new_sharded(num_shards)?),
+            encoding_db: DatabaseConnection::Local(EncodingDatabase::new_sharded(num_shards)?),
+            embedder: BertEmbedder::new()?,
+            embedding_cache: RwLock::new(HashMap::new()),
+            encoding_cache: RwLock::new(HashMap::new()),
         })
     }
 
@@ -81,6 +177,8 @@ impl Client {
             embedding_db: DatabaseConnection::Remote(Box::new(RemoteDatabase::new(embedding_url))),
             encoding_db: DatabaseConnection::Remote(Box::new(RemoteDatabase::new(encoding_url))),
             embedder: BertEmbedder::new()?,
+            embedding_cache: RwLock::new(HashMap::new()),
+            encoding_cache: RwLock::new(HashMap::new()),
         })
     }
 
@@ -88,6 +186,13 @@ impl Client {
     pub(crate) async fn update(&mut self) -> Result<()> {
         self.encoding_db.update().await?;
         self.embedding_db.update().await?;
+        // Warm shard 0's cache eagerly so a remote database rebuilt server-side (see the
+        // background refresh in `network::run_server`) is picked up here rather than only on
+        // the next query; the other shards of a sharded database warm lazily on first use.
+        tokio::try_join!(
+            fetch_shard_snapshot(&self.embedding_db, &self.embedding_cache, 0),
+            fetch_shard_snapshot(&self.encoding_db, &self.encoding_cache, 0),
+        )?;
         Ok(())
     }
 
@@ -105,58 +210,65 @@ impl Client {
         }
     }
 
+    /// Run one PIR lookup against embedding shard `shard`, returning that shard's full
+    /// relevance vector (one score per row local to the shard) alongside its snapshot.
+    async fn query_embedding_shard(
+        &self,
+        embedding: &DVector<BigInt>,
+        shard: usize,
+    ) -> Result<(DbSnapshot, DVector<BigInt>)> {
+        let snap = fetch_shard_snapshot(&self.embedding_db, &self.embedding_cache, shard).await?;
+        let adjusted_embedding = Self::adjust_embedding(embedding.clone(), snap.params.m);
+        let (s, query) = generate_query(&snap.params, &adjusted_embedding, &snap.a);
+
+        let response = self.embedding_db.shard_respond(shard, &query).await?;
+        let result = recover(&snap.hint, &s, &response, &snap.params);
+        Ok((snap, result))
+    }
+
     pub async fn query(&self, query: &str) -> Result<DVector<BigInt>> {
         let embedding = self
             .embedder
             .embed_text(query)
             .map_err(|e| PirError::Embedding(format!("Text embedding failed: {}", e)))?;
 
-        // Query embedding database
-        let embedding_params = self.embedding_db.params().await?;
-        let adjusted_embedding = Self::adjust_embedding(embedding, embedding_params.m);
-        let (s_embedding, query_embedding) = generate_query(
-            &embedding_params,
-            &adjusted_embedding,
-            &self.embedding_db.a().await?,
-        );
-
-        let response_embedding = self.embedding_db.respond(&query_embedding).await?;
-        let result_embedding = recover(
-            &self.embedding_db.hint().await?,
-            &s_embedding,
-            &response_embedding,
-            &embedding_params,
-        );
-
-        // Convert to one-hot vector
-        let result_vec = {
-            let mut vec = DVector::zeros(result_embedding.len());
-            let max_idx = result_embedding
-                .iter()
-                .enumerate()
-                .max_by_key(|(_i, val)| (*val).clone())
-                .ok_or_else(|| PirError::InvalidInput("Empty embedding result".to_string()))?
-                .0;
-            vec[max_idx] = BigInt::one();
+        // Fan the embedding query out to every shard concurrently: the corpus is partitioned
+        // across shards, so the best-matching record could live in any of them.
+        let num_shards = self.embedding_db.num_shards().await?;
+        let shard_results = try_join_all(
+            (0..num_shards).map(|shard| self.query_embedding_shard(&embedding, shard)),
+        )
+        .await?;
+
+        // Merge the per-shard argmax into one global winner.
+        let (winning_shard, local_idx) = shard_results
+            .iter()
+            .enumerate()
+            .flat_map(|(shard, (_snap, result))| {
+                result.iter().enumerate().map(move |(i, val)| (shard, i, val))
+            })
+            .max_by_key(|(_shard, _i, val)| (*val).clone())
+            .map(|(shard, i, _val)| (shard, i))
+            .ok_or_else(|| PirError::InvalidInput("Empty embedding result".to_string()))?;
+
+        // Query the encoding shard that holds the winning record. Embedding and encoding
+        // databases partition the same records identically, so the winning shard index lines
+        // up across both.
+        let encoding_snap =
+            fetch_shard_snapshot(&self.encoding_db, &self.encoding_cache, winning_shard).await?;
+        let one_hot = {
+            assert!(
+                local_idx < encoding_snap.params.n,
+                "winning record index out of range for its encoding shard"
+            );
+            let mut vec = DVector::zeros(encoding_snap.params.n);
+            vec[local_idx] = BigInt::one();
             vec
         };
+        let (s, query) = generate_query(&encoding_snap.params, &one_hot, &encoding_snap.a);
 
-        // Query encoding database
-        let encoding_params = self.encoding_db.params().await?;
-        let adjusted_result = Self::adjust_embedding(result_vec, encoding_params.m);
-        let (s, query) = generate_query(
-            &encoding_params,
-            &adjusted_result,
-            &self.encoding_db.a().await?,
-        );
-
-        let response = self.encoding_db.respond(&query).await?;
-        let result = recover(
-            &self.encoding_db.hint().await?,
-            &s,
-            &response,
-            &encoding_params,
-        );
+        let response = self.encoding_db.shard_respond(winning_shard, &query).await?;
+        let result = recover(&encoding_snap.hint, &s, &response, &encoding_snap.params);
 
         Ok(result)
     }
@@ -170,57 +282,69 @@ impl Client {
             .embedder
             .embed_text(query)
             .map_err(|e| PirError::Embedding(format!("Text embedding failed: {}", e)))?;
-        let embedding_params = self.embedding_db.params().await?;
-        let encoding_params = self.encoding_db.params().await?;
-
-        let (s_embedding, query_embedding) = generate_query(
-            &embedding_params,
-            &Self::adjust_embedding(embedding, embedding_params.m),
-            &self.embedding_db.a().await?,
-        );
-
-        let response_embedding = self.embedding_db.respond(&query_embedding).await?;
-        let result_embedding = recover(
-            &self.embedding_db.hint().await?,
-            &s_embedding,
-            &response_embedding,
-            &embedding_params,
-        );
-
-        let top_indices: Vec<usize> = {
-            let mut indexed_values: Vec<(usize, &BigInt)> =
-                result_embedding.iter().enumerate().collect();
-            indexed_values.sort_by(|(_i1, v1), (_i2, v2)| v2.cmp(v1));
-            indexed_values.into_iter().map(|(i, _val)| i).collect()
-        };
 
-        if top_indices.is_empty() {
+        let num_shards = self.embedding_db.num_shards().await?;
+        let shard_results = try_join_all(
+            (0..num_shards).map(|shard| self.query_embedding_shard(&embedding, shard)),
+        )
+        .await?;
+
+        // Rank every (shard, local index) pair globally and take the top k across all shards,
+        // not just within one.
+        let mut ranked: Vec<(usize, usize, BigInt)> = shard_results
+            .iter()
+            .enumerate()
+            .flat_map(|(shard, (_snap, result))| {
+                result
+                    .iter()
+                    .enumerate()
+                    .map(move |(i, val)| (shard, i, val.clone()))
+            })
+            .collect();
+        ranked.sort_by(|(_s1, _i1, v1), (_s2, _i2, v2)| v2.cmp(v1));
+
+        if ranked.is_empty() {
             return Err(PirError::InvalidInput("No results found".to_string()).into());
         }
 
-        let mut results = Vec::with_capacity(k);
-        for &idx in top_indices.iter().take(k) {
-            let mut vec = DVector::zeros(result_embedding.len());
-            vec[idx] = BigInt::one();
-
-            let (s, query) = generate_query(
-                &encoding_params,
-                &Self::adjust_embedding(vec, encoding_params.m),
-                &self.encoding_db.a().await?,
-            );
-
-            let response = self.encoding_db.respond(&query).await?;
-            let result = recover(
-                &self.encoding_db.hint().await?,
-                &s,
-                &response,
-                &encoding_params,
-            );
-
-            results.push(result);
+        // Group the top-k winners by their encoding shard so each shard can still be queried
+        // with a single batched round-trip.
+        let mut by_shard: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (shard, idx, _val) in ranked.into_iter().take(k) {
+            by_shard.entry(shard).or_default().push(idx);
         }
 
-        Ok(results)
+        let shard_batches = try_join_all(by_shard.into_iter().map(|(shard, local_indices)| {
+            let encoding_db = &self.encoding_db;
+            let encoding_cache = &self.encoding_cache;
+            async move {
+                let snap = fetch_shard_snapshot(encoding_db, encoding_cache, shard).await?;
+                let one_hot_vecs: Vec<DVector<BigInt>> = local_indices
+                    .iter()
+                    .map(|&idx| {
+                        assert!(
+                            idx < snap.params.n,
+                            "winning record index out of range for its encoding shard"
+                        );
+                        let mut vec = DVector::zeros(snap.params.n);
+                        vec[idx] = BigInt::one();
+                        vec
+                    })
+                    .collect();
+
+                let (secrets, query_matrix) =
+                    generate_query_batch(&snap.params, &one_hot_vecs, &snap.a);
+                let answers = encoding_db.shard_respond_batch(shard, &query_matrix).await?;
+                let results = recover_batch(&snap.hint, &secrets, &answers, &snap.params);
+
+                Ok::<Vec<DVector<BigInt>>, anyhow::Error>(
+                    results.column_iter().map(|col| col.into_owned()).collect(),
+                )
+            }
+        }))
+        .await?;
+
+        Ok(shard_batches.into_iter().flatten().collect())
     }
 }
 
@@ -264,6 +388,16 @@ mod tests {
         run_test_queries(&mut client).await
     }
 
+    // Regression test for sharding the encoding database along the wrong axis: the embedding
+    // DB's records sit on rows but the encoding DB's sit on columns (see `utils::encode_data`).
+    // Runs the same query loop as `test_local_client` against a 3-shard client; it checks that
+    // every query succeeds and its result decodes as UTF-8, not which record came back.
+    #[test]
+    async fn test_local_client_sharded() -> Result<()> {
+        let mut client = Client::new_local_sharded(3)?;
+        run_test_queries(&mut client).await
+    }
+
     #[ignore]
     #[test]
 
@@ -275,6 +409,30 @@ mod tests {
         run_test_queries(&mut client).await
     }
 
+    // Requires a live server (see `test_remote_client`). The server rebuilds its database on
+    // a timer (`network::run_server`'s 15s refresh loop), bumping its `version`; this checks
+    // that `update()` notices that and refreshes the client's cached hint/A/params instead of
+    // continuing to decode against the generation it started with.
+    #[ignore]
+    #[test]
+    async fn test_remote_client_tracks_version_updates() -> Result<()> {
+        let mut client = Client::new_remote(
+            "http://localhost:3001".to_string(),
+            "http://localhost:3000".to_string(),
+        )?;
+
+        // Warm the cache against whatever generation the server currently has.
+        client.query("Bitcoin").await?;
+
+        tokio::time::sleep(std::time::Duration::from_secs(16)).await;
+        client.update().await?;
+
+        let result = client.query("Bitcoin").await?;
+        let output = decode_input(&result)?;
+        println!("Decoded output after server-side rebuild: {:?}", output);
+        Ok(())
+    }
+
     #[test]
     async fn bench_client_retrieval_accuracy() -> Result<()> {
         fn names_match(name1: &str, name2: &str) -> bool {
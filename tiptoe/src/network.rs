@@ -1,23 +1,94 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use axum::{
-    extract::State,
+    extract::{Path, State},
     Json, Router,
 };
 use nalgebra::{DMatrix, DVector};
-use num_bigint::BigInt;
+use num_bigint::{BigInt, Sign};
 use num_traits::One;
 use reqwest::Client as HttpClient;
 use serde::{Deserialize, Serialize};
-use simplepir::{gen_params, generate_query, recover, SimplePIRParams};
-use std::{str::FromStr, sync::Arc, time::Duration};
-use tokio::sync::RwLock;
+use simplepir::{generate_query, recover, regenerate_matrix_a, SimplePIRParams};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{mpsc, oneshot, RwLock};
 
 use crate::{embedding::BertEmbedder, server::Database};
 
+/// A single `/query` (or `/shard/:shard/query`) request waiting to be folded into the next
+/// batched database scan for its shard.
+struct PendingQuery {
+    query: DVector<BigInt>,
+    reply: oneshot::Sender<DVector<BigInt>>,
+}
+
+/// Coalesces concurrent single-query requests against one shard into a single
+/// `shard_respond_batch` scan, so `N` clients querying at once pay for one pass over the
+/// database instead of `N`. Each caller still gets back exactly its own answer column;
+/// only the underlying scan is shared.
+struct QueryBatcher {
+    tx: mpsc::UnboundedSender<PendingQuery>,
+}
+
+impl QueryBatcher {
+    /// How long a batch waits for more queries to join before it scans, once the first one
+    /// arrives.
+    const WINDOW: Duration = Duration::from_millis(5);
+    /// Largest batch the queue will accumulate before scanning early.
+    const MAX_BATCH: usize = 64;
+
+    fn new<T: Database + Send + Sync + 'static>(db: Arc<RwLock<T>>, shard: usize) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<PendingQuery>();
+
+        tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                let mut pending = vec![first];
+
+                let deadline = tokio::time::sleep(Self::WINDOW);
+                tokio::pin!(deadline);
+                while pending.len() < Self::MAX_BATCH {
+                    tokio::select! {
+                        _ = &mut deadline => break,
+                        maybe_next = rx.recv() => match maybe_next {
+                            Some(next) => pending.push(next),
+                            None => break,
+                        },
+                    }
+                }
+
+                let queries = DMatrix::from_columns(
+                    &pending.iter().map(|p| p.query.clone()).collect::<Vec<_>>(),
+                );
+
+                let db = db.read().await;
+                match db.shard_respond_batch(shard, &queries) {
+                    Ok(answers) => {
+                        for (i, p) in pending.into_iter().enumerate() {
+                            let _ = p.reply.send(answers.column(i).into_owned());
+                        }
+                    }
+                    Err(e) => eprintln!("batched query scan failed: {:?}", e),
+                }
+            }
+        });
+
+        Self { tx }
+    }
+
+    async fn submit(&self, query: DVector<BigInt>) -> Result<DVector<BigInt>> {
+        let (reply, rx) = oneshot::channel();
+        self.tx
+            .send(PendingQuery { query, reply })
+            .map_err(|_| anyhow!("query batcher task has stopped"))?;
+        rx.await.map_err(|_| anyhow!("query batcher dropped the reply"))
+    }
+}
+
 // Shared state for server
 pub struct ServerState<T: Database + Send + Sync> {
-    db: RwLock<T>,
+    db: Arc<RwLock<T>>,
+    /// One query batcher per shard (a single-matrix database is just the one-shard case).
+    batchers: Vec<QueryBatcher>,
 }
 
 // Request/Response types
@@ -32,11 +103,23 @@ pub struct QueryResponse {
 }
 
 #[derive(Serialize, Deserialize)]
-pub struct ParamsData {
-    m: usize,
-    n: usize,
-    q: String,
-    p: String,
+pub struct QueryBatchRequest {
+    queries: MatrixResponse, // Query vectors, one per column
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct QueryBatchResponse {
+    answers: MatrixResponse, // Answer vectors, one per column
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VersionResponse {
+    version: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NumShardsResponse {
+    num_shards: usize,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -46,6 +129,19 @@ pub struct MatrixResponse {
     data: Vec<String>,
 }
 
+/// Wire format for the `hint` matrix: each `BigInt` is packed as a sign byte, a little-endian
+/// `u32` limb count, then that many little-endian `u32` limbs, all concatenated column-major
+/// (nalgebra's storage order). This avoids printing every entry through `BigInt::to_string`,
+/// which is what [`MatrixResponse`] does for the much smaller query/answer vectors. The byte
+/// buffer itself is still sent inside a JSON body, as an array of decimal byte values, so the
+/// response is not smaller than the decimal-string form.
+#[derive(Serialize, Deserialize)]
+pub struct HintResponse {
+    rows: usize,
+    cols: usize,
+    data: Vec<u8>,
+}
+
 // Helper functions for serialization
 fn serialize_vector(vec: &DVector<BigInt>) -> Vec<String> {
     vec.iter().map(|x| x.to_string()).collect()
@@ -69,25 +165,55 @@ fn deserialize_matrix(response: &MatrixResponse) -> DMatrix<BigInt> {
     DMatrix::from_vec(response.rows, response.cols, data)
 }
 
-fn serialize_params(params: &SimplePIRParams) -> ParamsData {
-    ParamsData {
-        m: params.m,
-        n: params.n,
-        q: params.q.to_string(),
-        p: params.p.to_string(),
+fn encode_bigint(x: &BigInt, buf: &mut Vec<u8>) {
+    let (sign, limbs) = x.to_u32_digits();
+    buf.push(if sign == Sign::Minus { 1 } else { 0 });
+    buf.extend_from_slice(&(limbs.len() as u32).to_le_bytes());
+    for limb in limbs {
+        buf.extend_from_slice(&limb.to_le_bytes());
     }
 }
 
-fn deserialize_params(data: &ParamsData) -> SimplePIRParams {
-    let p = BigInt::from_str(&data.p).unwrap();
-    let mod_power = (p.bits() - 1) as u32;
-    gen_params(data.m, data.n, mod_power)
+fn decode_bigint(buf: &[u8], pos: &mut usize) -> BigInt {
+    let sign = if buf[*pos] == 1 { Sign::Minus } else { Sign::Plus };
+    *pos += 1;
+    let len = u32::from_le_bytes(buf[*pos..*pos + 4].try_into().unwrap()) as usize;
+    *pos += 4;
+    let limbs: Vec<u32> = buf[*pos..*pos + len * 4]
+        .chunks_exact(4)
+        .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+        .collect();
+    *pos += len * 4;
+    BigInt::from_slice(sign, &limbs)
+}
+
+fn serialize_hint(matrix: &DMatrix<BigInt>) -> HintResponse {
+    let mut data = Vec::new();
+    for x in matrix.iter() {
+        encode_bigint(x, &mut data);
+    }
+    HintResponse {
+        rows: matrix.nrows(),
+        cols: matrix.ncols(),
+        data,
+    }
+}
+
+fn deserialize_hint(response: &HintResponse) -> DMatrix<BigInt> {
+    let mut pos = 0;
+    let data: Vec<BigInt> = (0..response.rows * response.cols)
+        .map(|_| decode_bigint(&response.data, &mut pos))
+        .collect();
+    DMatrix::from_vec(response.rows, response.cols, data)
 }
 
 pub async fn run_server<T: Database + Send + Sync + 'static>(db: T, port: u16) {
-    let state = Arc::new(ServerState {
-        db: RwLock::new(db),
-    });
+    let num_shards = db.num_shards();
+    let db = Arc::new(RwLock::new(db));
+    let batchers = (0..num_shards)
+        .map(|shard| QueryBatcher::new(Arc::clone(&db), shard))
+        .collect();
+    let state = Arc::new(ServerState { db, batchers });
 
     let update_state = Arc::clone(&state);
     tokio::spawn(async move {
@@ -125,9 +251,27 @@ pub async fn run_server<T: Database + Send + Sync + 'static>(db: T, port: u16) {
 
     let app = Router::new()
         .route("/query", axum::routing::post(handle_query::<T>))
+        .route("/query_batch", axum::routing::post(handle_query_batch::<T>))
         .route("/params", axum::routing::get(handle_params::<T>))
         .route("/hint", axum::routing::get(handle_hint::<T>))
-        .route("/a", axum::routing::get(handle_a::<T>))
+        .route("/version", axum::routing::get(handle_version::<T>))
+        .route("/shards", axum::routing::get(handle_num_shards::<T>))
+        .route(
+            "/shard/:shard/query",
+            axum::routing::post(handle_shard_query::<T>),
+        )
+        .route(
+            "/shard/:shard/query_batch",
+            axum::routing::post(handle_shard_query_batch::<T>),
+        )
+        .route(
+            "/shard/:shard/params",
+            axum::routing::get(handle_shard_params::<T>),
+        )
+        .route(
+            "/shard/:shard/hint",
+            axum::routing::get(handle_shard_hint::<T>),
+        )
         .with_state(state);
 
     let addr = format!("0.0.0.0:{}", port).parse().unwrap();
@@ -144,41 +288,136 @@ async fn handle_query<T: Database + Send + Sync>(
     Json(request): Json<QueryRequest>,
 ) -> Json<QueryResponse> {
     let query = deserialize_vector(&request.query);
-    let db = state.db.read().await;
-    let response = db.respond(&query).unwrap();
+    let response = state.batchers[0].submit(query).await.unwrap();
     Json(QueryResponse {
         response: serialize_vector(&response),
     })
 }
 
+async fn handle_query_batch<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+    Json(request): Json<QueryBatchRequest>,
+) -> Json<QueryBatchResponse> {
+    let queries = deserialize_matrix(&request.queries);
+    let db = state.db.read().await;
+    let answers = db.respond_batch(&queries).unwrap();
+    Json(QueryBatchResponse {
+        answers: serialize_matrix(&answers),
+    })
+}
+
 async fn handle_params<T: Database + Send + Sync>(
     State(state): State<Arc<ServerState<T>>>,
-) -> Json<ParamsData> {
+) -> Json<SimplePIRParams> {
     let db = state.db.read().await;
-    Json(serialize_params(db.params()))
+    Json(db.params().clone())
 }
 
 async fn handle_hint<T: Database + Send + Sync>(
     State(state): State<Arc<ServerState<T>>>,
-) -> Json<MatrixResponse> {
+) -> Json<HintResponse> {
+    let db = state.db.read().await;
+    Json(serialize_hint(db.hint()))
+}
+
+async fn handle_version<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+) -> Json<VersionResponse> {
+    let db = state.db.read().await;
+    Json(VersionResponse {
+        version: db.version(),
+    })
+}
+
+async fn handle_num_shards<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+) -> Json<NumShardsResponse> {
+    let db = state.db.read().await;
+    Json(NumShardsResponse {
+        num_shards: db.num_shards(),
+    })
+}
+
+async fn handle_shard_query<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+    Path(shard): Path<usize>,
+    Json(request): Json<QueryRequest>,
+) -> Json<QueryResponse> {
+    let query = deserialize_vector(&request.query);
+    let response = state.batchers[shard].submit(query).await.unwrap();
+    Json(QueryResponse {
+        response: serialize_vector(&response),
+    })
+}
+
+async fn handle_shard_query_batch<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+    Path(shard): Path<usize>,
+    Json(request): Json<QueryBatchRequest>,
+) -> Json<QueryBatchResponse> {
+    let queries = deserialize_matrix(&request.queries);
     let db = state.db.read().await;
-    Json(serialize_matrix(db.hint()))
+    let answers = db.shard_respond_batch(shard, &queries).unwrap();
+    Json(QueryBatchResponse {
+        answers: serialize_matrix(&answers),
+    })
+}
+
+async fn handle_shard_params<T: Database + Send + Sync>(
+    State(state): State<Arc<ServerState<T>>>,
+    Path(shard): Path<usize>,
+) -> Json<SimplePIRParams> {
+    let db = state.db.read().await;
+    Json(db.shard_params(shard).clone())
 }
 
-async fn handle_a<T: Database + Send + Sync>(
+async fn handle_shard_hint<T: Database + Send + Sync>(
     State(state): State<Arc<ServerState<T>>>,
-) -> Json<MatrixResponse> {
+    Path(shard): Path<usize>,
+) -> Json<HintResponse> {
     let db = state.db.read().await;
-    Json(serialize_matrix(db.a()))
+    Json(serialize_hint(db.shard_hint(shard)))
 }
 
 // Remote database implementation that connects to server
 #[async_trait]
 pub trait AsyncDatabase {
     async fn respond(&self, query: &DVector<BigInt>) -> Result<DVector<BigInt>>;
+    async fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>>;
     async fn get_params(&self) -> Result<SimplePIRParams>;
     async fn get_hint(&self) -> Result<DMatrix<BigInt>>;
-    async fn get_a(&self) -> Result<DMatrix<BigInt>>;
+    async fn get_version(&self) -> Result<u64>;
+
+    /// Number of shards behind this remote database. The default reports one (the
+    /// single-matrix `N = 1` case); [`RemoteDatabase`] overrides it to ask the server's
+    /// `/shards` endpoint.
+    async fn get_num_shards(&self) -> Result<usize> {
+        Ok(1)
+    }
+
+    async fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded remote database");
+        self.respond(query).await
+    }
+
+    async fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded remote database");
+        self.respond_batch(queries).await
+    }
+
+    async fn shard_params(&self, shard: usize) -> Result<SimplePIRParams> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded remote database");
+        self.get_params().await
+    }
+
+    async fn shard_hint(&self, shard: usize) -> Result<DMatrix<BigInt>> {
+        assert_eq!(shard, 0, "shard index out of range for an unsharded remote database");
+        self.get_hint().await
+    }
 }
 
 pub struct RemoteDatabase {
@@ -212,37 +451,119 @@ impl AsyncDatabase for RemoteDatabase {
         Ok(deserialize_vector(&response.response))
     }
 
+    async fn respond_batch(&self, queries: &DMatrix<BigInt>) -> Result<DMatrix<BigInt>> {
+        let response: QueryBatchResponse = self
+            .client
+            .post(format!("{}/query_batch", self.base_url))
+            .json(&QueryBatchRequest {
+                queries: serialize_matrix(queries),
+            })
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(deserialize_matrix(&response.answers))
+    }
+
     async fn get_params(&self) -> Result<SimplePIRParams> {
-        let response: ParamsData = self
+        let params: SimplePIRParams = self
             .client
             .get(format!("{}/params", self.base_url))
             .send()
             .await?
             .json()
             .await?;
-        Ok(deserialize_params(&response))
+        Ok(params)
     }
 
     async fn get_hint(&self) -> Result<DMatrix<BigInt>> {
-        let response: MatrixResponse = self
+        let response: HintResponse = self
             .client
             .get(format!("{}/hint", self.base_url))
             .send()
             .await?
             .json()
             .await?;
-        Ok(deserialize_matrix(&response))
+        Ok(deserialize_hint(&response))
+    }
+
+    async fn get_version(&self) -> Result<u64> {
+        let response: VersionResponse = self
+            .client
+            .get(format!("{}/version", self.base_url))
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(response.version)
+    }
+
+    async fn get_num_shards(&self) -> Result<usize> {
+        let response: NumShardsResponse = self
+            .client
+            .get(format!("{}/shards", self.base_url))
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(response.num_shards)
+    }
+
+    async fn shard_respond(&self, shard: usize, query: &DVector<BigInt>) -> Result<DVector<BigInt>> {
+        let response: QueryResponse = self
+            .client
+            .post(format!("{}/shard/{}/query", self.base_url, shard))
+            .json(&QueryRequest {
+                query: serialize_vector(query),
+            })
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(deserialize_vector(&response.response))
+    }
+
+    async fn shard_respond_batch(
+        &self,
+        shard: usize,
+        queries: &DMatrix<BigInt>,
+    ) -> Result<DMatrix<BigInt>> {
+        let response: QueryBatchResponse = self
+            .client
+            .post(format!("{}/shard/{}/query_batch", self.base_url, shard))
+            .json(&QueryBatchRequest {
+                queries: serialize_matrix(queries),
+            })
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        Ok(deserialize_matrix(&response.answers))
+    }
+
+    async fn shard_params(&self, shard: usize) -> Result<SimplePIRParams> {
+        let params: SimplePIRParams = self
+            .client
+            .get(format!("{}/shard/{}/params", self.base_url, shard))
+            .send()
+            .await?
+            .json()
+            .await?;
+        Ok(params)
     }
 
-    async fn get_a(&self) -> Result<DMatrix<BigInt>> {
-        let response: MatrixResponse = self
+    async fn shard_hint(&self, shard: usize) -> Result<DMatrix<BigInt>> {
+        let response: HintResponse = self
             .client
-            .get(format!("{}/a", self.base_url))
+            .get(format!("{}/shard/{}/hint", self.base_url, shard))
             .send()
             .await?
             .json()
             .await?;
-        Ok(deserialize_matrix(&response))
+        Ok(deserialize_hint(&response))
     }
 }
 
@@ -284,7 +605,7 @@ impl NetworkClient {
         let (s_embedding, query_embedding) = generate_query(
             &embedding_params,
             &adjusted_embedding,
-            &self.embedding_db.get_a().await?,
+            &regenerate_matrix_a(&embedding_params),
         );
 
         let response_embedding = self.embedding_db.respond(&query_embedding).await?;
@@ -312,7 +633,7 @@ impl NetworkClient {
         let (s, query) = generate_query(
             &encoding_params,
             &adjusted_result,
-            &self.encoding_db.get_a().await?,
+            &regenerate_matrix_a(&encoding_params),
         );
 
         let response = self.encoding_db.respond(&query).await?;